@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use network;
+
+use super::reservation::SignatureReservation;
+use super::transaction::{Transaction, Transfer};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum AccountingError {
+    InsufficientFunds,
+    InvalidTransfer,
+    InvalidTransferSignature,
+    SendError,
+}
+
+pub struct Accountant {
+    balances: Mutex<HashMap<String, u64>>,
+    reservation: Mutex<SignatureReservation>,
+    pending: Mutex<Vec<Transaction>>,
+    collected_fees: Mutex<u64>,
+}
+
+impl Default for Accountant {
+    fn default() -> Self {
+        Accountant::new()
+    }
+}
+
+impl Accountant {
+    pub fn new() -> Self {
+        Accountant {
+            balances: Mutex::new(HashMap::new()),
+            reservation: Mutex::new(SignatureReservation::default()),
+            pending: Mutex::new(Vec::new()),
+            collected_fees: Mutex::new(0),
+        }
+    }
+
+    pub fn balance(&self, address: &str) -> u64 {
+        *self.balances
+            .lock()
+            .expect("Balance map lock poisoned")
+            .get(address)
+            .unwrap_or(&0)
+    }
+
+    /// Admits a block's hash into the replay-protection window so transfers
+    /// referencing it as `last_id` can start validating. Must be called for
+    /// every new block (including the genesis block) before its descendants'
+    /// transfers are processed, or `reserve_signature` never has a window to
+    /// reserve against and every transfer is rejected.
+    pub fn register_block(&self, block_id: String) {
+        self.reservation
+            .lock()
+            .expect("Reservation lock poisoned")
+            .register_block(block_id);
+    }
+
+    /// Sum of `fee` across every transfer processed since the last `cut_block`,
+    /// i.e. the amount the next block's reward is entitled to collect.
+    pub fn pending_fees(&self) -> u64 {
+        *self.collected_fees.lock().expect("Collected fees lock poisoned")
+    }
+
+    /// Validates a pre-signed transaction and, if it passes signature,
+    /// replay and balance checks, queues it for inclusion in the next block.
+    ///
+    /// A `Transaction::Reward` is always rejected here: only `cut_block`
+    /// knows the fees a block actually collected, so a reward can only enter
+    /// the ledger by minting it there, never by submitting one directly.
+    pub fn process_transaction(&self, tx: Transaction) -> Result<(), AccountingError> {
+        match tx {
+            Transaction::Transfer(ref transfer) => {
+                self.process_transfer(transfer, &tx)?;
+                self.enqueue(tx)
+            }
+            Transaction::Reward(_) => Err(AccountingError::InvalidTransfer),
+        }
+    }
+
+    /// Drains the fees collected since the last call into a `Transaction::reward`
+    /// for `recipient`, credits it, admits `block_id` into the replay-protection
+    /// window, and queues the reward for inclusion in the next block.
+    pub fn cut_block(&self, recipient: String, block_id: String) -> Result<Transaction, AccountingError> {
+        let reward = Transaction::reward(recipient, self.pending_fees());
+
+        // Enqueue before mutating fees/balance/registration: if this fails
+        // (only on a poisoned `pending` lock) the block is never considered
+        // cut, so nothing else about it should have taken effect either.
+        self.enqueue(reward.clone())?;
+
+        *self.collected_fees.lock().expect("Collected fees lock poisoned") = 0;
+        if let Transaction::Reward(ref r) = reward {
+            self.credit(r.recipient(), r.amount());
+        }
+        self.register_block(block_id);
+
+        Ok(reward)
+    }
+
+    fn process_transfer(&self, transfer: &Transfer, tx: &Transaction) -> Result<(), AccountingError> {
+        // A `Transfer` ignores the `collected_fees` parameter, so any value
+        // is fine here; `0` keeps this call site honest about not having one.
+        if !tx.is_valid(0) {
+            return Err(AccountingError::InvalidTransferSignature);
+        }
+
+        let total_spent = transfer.amount() + transfer.fee();
+        let mut balances = self.balances.lock().expect("Balance map lock poisoned");
+        let sender_balance = *balances.get(transfer.sender()).unwrap_or(&0);
+        if sender_balance < total_spent {
+            return Err(AccountingError::InsufficientFunds);
+        }
+
+        // Only reserve the signature once the transfer is known to be
+        // affordable, so a transfer that fails on funds can still be
+        // retried later with the same signature once the sender is funded.
+        let reserved = self
+            .reservation
+            .lock()
+            .expect("Reservation lock poisoned")
+            .reserve_signature(transfer.last_id(), transfer.signature());
+        if !reserved {
+            return Err(AccountingError::InvalidTransfer);
+        }
+
+        *balances
+            .entry(String::from(transfer.sender()))
+            .or_insert(0) -= total_spent;
+        *balances
+            .entry(String::from(transfer.recipient()))
+            .or_insert(0) += transfer.amount();
+        drop(balances);
+
+        *self.collected_fees.lock().expect("Collected fees lock poisoned") += transfer.fee();
+        Ok(())
+    }
+
+    fn credit(&self, address: &str, amount: u64) {
+        *self.balances
+            .lock()
+            .expect("Balance map lock poisoned")
+            .entry(String::from(address))
+            .or_insert(0) += amount;
+    }
+
+    fn enqueue(&self, tx: Transaction) -> Result<(), AccountingError> {
+        match self.pending.lock() {
+            Ok(mut pending) => {
+                pending.push(tx);
+                Ok(())
+            }
+            Err(_) => Err(AccountingError::SendError),
+        }
+    }
+}
+
+/// Network-facing entry point for clients that hold their own keys: accept an
+/// already-signed `network::Transfer` and hand it to the accountant without
+/// the node ever touching a private key.
+pub fn submit_signed(accountant: &Accountant, transfer: network::Transfer) -> Result<(), AccountingError> {
+    let transfer = Transfer::from(transfer);
+    accountant.process_transaction(Transaction::Transfer(transfer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::transaction::MINER_REWARD;
+    use super::super::wallet::Wallet;
+
+    #[test]
+    fn insufficient_funds_does_not_consume_the_signature() {
+        let (wallet, _) = Wallet::generate();
+        let accountant = Accountant::new();
+        accountant.register_block(String::from("block-1"));
+
+        let tx = Transaction::transfer(&wallet, "recipient-address", 100, 0, "block-1", None);
+        assert_eq!(
+            accountant.process_transaction(tx.clone()),
+            Err(AccountingError::InsufficientFunds)
+        );
+
+        // Funding the sender and retrying the identical signed transfer must
+        // succeed: a failed-for-funds attempt must not have reserved the
+        // signature against replay.
+        accountant.credit(&wallet.public_key_hex(), 100);
+        assert_eq!(accountant.process_transaction(tx), Ok(()));
+    }
+
+    #[test]
+    fn rejects_a_transfer_whose_last_id_was_never_registered() {
+        let (wallet, _) = Wallet::generate();
+        let accountant = Accountant::new();
+        accountant.credit(&wallet.public_key_hex(), 100);
+
+        let tx = Transaction::transfer(&wallet, "recipient-address", 10, 0, "unknown-block", None);
+        assert_eq!(
+            accountant.process_transaction(tx),
+            Err(AccountingError::InvalidTransfer)
+        );
+    }
+
+    #[test]
+    fn transfer_fee_is_collected_and_moves_balances() {
+        let (wallet, _) = Wallet::generate();
+        let accountant = Accountant::new();
+        accountant.register_block(String::from("block-1"));
+        accountant.credit(&wallet.public_key_hex(), 100);
+
+        let tx = Transaction::transfer(&wallet, "recipient-address", 10, 3, "block-1", None);
+        assert_eq!(accountant.process_transaction(tx), Ok(()));
+
+        assert_eq!(accountant.balance("recipient-address"), 10);
+        assert_eq!(accountant.balance(&wallet.public_key_hex()), 87);
+        assert_eq!(accountant.pending_fees(), 3);
+    }
+
+    #[test]
+    fn rejects_a_reward_submitted_directly() {
+        let accountant = Accountant::new();
+        let reward = Transaction::reward(String::from("miner-address"), 0);
+
+        assert_eq!(
+            accountant.process_transaction(reward),
+            Err(AccountingError::InvalidTransfer)
+        );
+        assert_eq!(accountant.balance("miner-address"), 0);
+    }
+
+    #[test]
+    fn cut_block_credits_the_reward_and_resets_pending_fees() {
+        let (wallet, _) = Wallet::generate();
+        let accountant = Accountant::new();
+        accountant.register_block(String::from("block-1"));
+        accountant.credit(&wallet.public_key_hex(), 100);
+
+        let tx = Transaction::transfer(&wallet, "recipient-address", 10, 3, "block-1", None);
+        assert_eq!(accountant.process_transaction(tx), Ok(()));
+        assert_eq!(accountant.pending_fees(), 3);
+
+        let reward = accountant
+            .cut_block(String::from("miner-address"), String::from("block-2"))
+            .expect("cut_block should succeed");
+        match reward {
+            Transaction::Reward(ref reward) => {
+                assert_eq!(reward.recipient(), "miner-address");
+                assert_eq!(reward.amount(), MINER_REWARD + 3);
+            }
+            Transaction::Transfer(_) => panic!("cut_block must return a Reward"),
+        }
+        assert_eq!(accountant.balance("miner-address"), MINER_REWARD + 3);
+        assert_eq!(accountant.pending_fees(), 0);
+
+        // cut_block admitted "block-2", so a transfer can reference it as last_id.
+        let tx = Transaction::transfer(&wallet, "recipient-address", 5, 0, "block-2", None);
+        assert_eq!(accountant.process_transaction(tx), Ok(()));
+    }
+}
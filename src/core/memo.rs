@@ -0,0 +1,107 @@
+use aes_gcm::aead::{Aead, NewAead};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::{Digest, Sha512};
+use x25519_dalek::{EphemeralSecret, PublicKey, StaticSecret};
+
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct EncryptedBlob {
+    ephemeral_pubkey: [u8; 32],
+    nonce: [u8; NONCE_LEN],
+    ciphertext: Vec<u8>,
+    tag: [u8; TAG_LEN],
+}
+
+impl EncryptedBlob {
+    pub(crate) fn canonical_bytes(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        super::canonical::write_bytes(&mut buffer, &self.ephemeral_pubkey);
+        super::canonical::write_bytes(&mut buffer, &self.nonce);
+        super::canonical::write_bytes(&mut buffer, &self.ciphertext);
+        super::canonical::write_bytes(&mut buffer, &self.tag);
+        buffer
+    }
+
+    pub fn encrypt(plaintext: &[u8], recipient_public_key: &PublicKey) -> Self {
+        let ephemeral_secret = EphemeralSecret::new(OsRng);
+        let ephemeral_pubkey = PublicKey::from(&ephemeral_secret);
+        let shared_secret = ephemeral_secret.diffie_hellman(recipient_public_key);
+        let cipher = shared_secret_cipher(shared_secret.as_bytes());
+
+        let mut nonce_bytes = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce_bytes);
+
+        let mut sealed = cipher
+            .encrypt(Nonce::from_slice(&nonce_bytes), plaintext)
+            .expect("Memo encryption failed");
+        let tag_bytes = sealed.split_off(sealed.len() - TAG_LEN);
+        let mut tag = [0u8; TAG_LEN];
+        tag.copy_from_slice(&tag_bytes);
+
+        EncryptedBlob {
+            ephemeral_pubkey: ephemeral_pubkey.to_bytes(),
+            nonce: nonce_bytes,
+            ciphertext: sealed,
+            tag,
+        }
+    }
+
+    pub fn decrypt(&self, recipient_secret_key: &StaticSecret) -> Option<Vec<u8>> {
+        let ephemeral_pubkey = PublicKey::from(self.ephemeral_pubkey);
+        let shared_secret = recipient_secret_key.diffie_hellman(&ephemeral_pubkey);
+        let cipher = shared_secret_cipher(shared_secret.as_bytes());
+
+        let mut sealed = self.ciphertext.clone();
+        sealed.extend_from_slice(&self.tag);
+        cipher.decrypt(Nonce::from_slice(&self.nonce), sealed.as_ref()).ok()
+    }
+}
+
+fn shared_secret_cipher(shared_secret: &[u8]) -> Aes256Gcm {
+    let key_bytes = Sha512::digest(shared_secret);
+    Aes256Gcm::new(Key::from_slice(&key_bytes[..32]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_plaintext_through_encrypt_and_decrypt() {
+        let recipient_secret = StaticSecret::new(OsRng);
+        let recipient_public = PublicKey::from(&recipient_secret);
+
+        let blob = EncryptedBlob::encrypt(b"hello recipient", &recipient_public);
+        let plaintext = blob
+            .decrypt(&recipient_secret)
+            .expect("The intended recipient must be able to decrypt");
+
+        assert_eq!(plaintext, b"hello recipient");
+    }
+
+    #[test]
+    fn wrong_recipient_cannot_decrypt() {
+        let recipient_secret = StaticSecret::new(OsRng);
+        let recipient_public = PublicKey::from(&recipient_secret);
+        let eavesdropper_secret = StaticSecret::new(OsRng);
+
+        let blob = EncryptedBlob::encrypt(b"hello recipient", &recipient_public);
+
+        assert!(blob.decrypt(&eavesdropper_secret).is_none());
+    }
+
+    #[test]
+    fn tampered_ciphertext_fails_to_decrypt() {
+        let recipient_secret = StaticSecret::new(OsRng);
+        let recipient_public = PublicKey::from(&recipient_secret);
+
+        let mut blob = EncryptedBlob::encrypt(b"hello recipient", &recipient_public);
+        blob.ciphertext[0] ^= 0xff;
+
+        assert!(blob.decrypt(&recipient_secret).is_none());
+    }
+}
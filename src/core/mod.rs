@@ -0,0 +1,7 @@
+pub mod accountant;
+pub mod canonical;
+pub mod memo;
+pub mod reservation;
+pub mod sig_scheme;
+pub mod transaction;
+pub mod wallet;
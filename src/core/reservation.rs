@@ -0,0 +1,75 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+const DEFAULT_WINDOW_SIZE: usize = 64;
+
+pub struct SignatureReservation {
+    window_size: usize,
+    recent_block_ids: VecDeque<String>,
+    seen_signatures: HashMap<String, HashSet<String>>,
+}
+
+impl Default for SignatureReservation {
+    fn default() -> Self {
+        SignatureReservation::new(DEFAULT_WINDOW_SIZE)
+    }
+}
+
+impl SignatureReservation {
+    pub fn new(window_size: usize) -> Self {
+        SignatureReservation {
+            window_size,
+            recent_block_ids: VecDeque::with_capacity(window_size),
+            seen_signatures: HashMap::with_capacity(window_size),
+        }
+    }
+
+    pub fn register_block(&mut self, block_id: String) {
+        self.seen_signatures
+            .entry(block_id.clone())
+            .or_default();
+        self.recent_block_ids.push_back(block_id);
+
+        if self.recent_block_ids.len() > self.window_size {
+            if let Some(aged_out) = self.recent_block_ids.pop_front() {
+                self.seen_signatures.remove(&aged_out);
+            }
+        }
+    }
+
+    pub fn reserve_signature(&mut self, last_id: &str, signature: &str) -> bool {
+        match self.seen_signatures.get_mut(last_id) {
+            Some(signatures) => signatures.insert(String::from(signature)),
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_last_id_that_was_never_registered() {
+        let mut reservation = SignatureReservation::new(2);
+        assert!(!reservation.reserve_signature("block-1", "sig-a"));
+    }
+
+    #[test]
+    fn rejects_a_replayed_signature_within_the_window() {
+        let mut reservation = SignatureReservation::new(2);
+        reservation.register_block(String::from("block-1"));
+
+        assert!(reservation.reserve_signature("block-1", "sig-a"));
+        assert!(!reservation.reserve_signature("block-1", "sig-a"));
+    }
+
+    #[test]
+    fn rejects_a_last_id_once_it_ages_out_of_the_window() {
+        let mut reservation = SignatureReservation::new(1);
+        reservation.register_block(String::from("block-1"));
+        reservation.register_block(String::from("block-2"));
+
+        assert!(!reservation.reserve_signature("block-1", "sig-a"));
+        assert!(reservation.reserve_signature("block-2", "sig-a"));
+    }
+}
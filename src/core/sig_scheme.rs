@@ -0,0 +1,135 @@
+use hex::{FromHex, ToHex};
+use ring::signature as ed25519;
+use secp256k1::recovery::{RecoverableSignature, RecoveryId};
+use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+use tiny_keccak::keccak256;
+use untrusted;
+
+pub trait SigScheme {
+    fn sign(message: &[u8], key: &[u8]) -> String;
+    fn verify(message: &[u8], signature: &str, signer: &str) -> bool;
+}
+
+pub struct Ed25519Scheme;
+
+impl SigScheme for Ed25519Scheme {
+    fn sign(message: &[u8], key: &[u8]) -> String {
+        let key_pair = ed25519::Ed25519KeyPair::from_pkcs8(untrusted::Input::from(key))
+            .expect("Cannot create private/public key pair");
+        key_pair.sign(message).to_hex()
+    }
+
+    fn verify(message: &[u8], signature: &str, signer: &str) -> bool {
+        let public_key_bytes = match Vec::from_hex(signer) {
+            Ok(bytes) => bytes,
+            Err(_) => return false,
+        };
+        let signature_bytes = match Vec::from_hex(signature) {
+            Ok(bytes) => bytes,
+            Err(_) => return false,
+        };
+        ed25519::verify(
+            &ed25519::ED25519,
+            untrusted::Input::from(&public_key_bytes),
+            untrusted::Input::from(message),
+            untrusted::Input::from(&signature_bytes),
+        ).is_ok()
+    }
+}
+
+pub struct Secp256k1RecoverableScheme;
+
+impl Secp256k1RecoverableScheme {
+    pub fn address(public_key: &PublicKey) -> String {
+        let uncompressed = public_key.serialize_uncompressed();
+        let hash = keccak256(&uncompressed[1..]);
+        (&hash[12..]).to_hex()
+    }
+}
+
+impl SigScheme for Secp256k1RecoverableScheme {
+    fn sign(message: &[u8], key: &[u8]) -> String {
+        let secp = Secp256k1::signing_only();
+        let secret_key = SecretKey::from_slice(key).expect("Invalid secp256k1 private key");
+        let msg = Message::from_slice(&keccak256(message)).expect("Hash is always 32 bytes");
+
+        let recoverable_sig = secp.sign_recoverable(&msg, &secret_key);
+        let (recovery_id, sig_bytes) = recoverable_sig.serialize_compact();
+
+        let mut bytes = Vec::with_capacity(65);
+        bytes.extend_from_slice(&sig_bytes);
+        bytes.push(recovery_id.to_i32() as u8);
+        bytes.to_hex()
+    }
+
+    fn verify(message: &[u8], signature: &str, signer: &str) -> bool {
+        let sig_bytes = match Vec::from_hex(signature) {
+            Ok(bytes) => bytes,
+            Err(_) => return false,
+        };
+        if sig_bytes.len() != 65 {
+            return false;
+        }
+        let (sig, recovery_byte) = sig_bytes.split_at(64);
+        let recovery_id = match RecoveryId::from_i32(i32::from(recovery_byte[0])) {
+            Ok(id) => id,
+            Err(_) => return false,
+        };
+        let recoverable_sig = match RecoverableSignature::from_compact(sig, recovery_id) {
+            Ok(sig) => sig,
+            Err(_) => return false,
+        };
+
+        let secp = Secp256k1::verification_only();
+        let msg = Message::from_slice(&keccak256(message)).expect("Hash is always 32 bytes");
+        match secp.recover(&msg, &recoverable_sig) {
+            Ok(public_key) => Secp256k1RecoverableScheme::address(&public_key) == signer,
+            Err(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secp256k1::rand::rngs::OsRng;
+
+    #[test]
+    fn recovers_the_signer_address_from_a_valid_signature() {
+        let secp = Secp256k1::new();
+        let mut rng = OsRng::new().expect("Failed to obtain OS RNG");
+        let (secret_key, public_key) = secp.generate_keypair(&mut rng);
+        let address = Secp256k1RecoverableScheme::address(&public_key);
+
+        let message = b"canonical transfer bytes";
+        let signature = Secp256k1RecoverableScheme::sign(message, &secret_key[..]);
+
+        assert!(Secp256k1RecoverableScheme::verify(message, &signature, &address));
+    }
+
+    #[test]
+    fn rejects_a_signature_recovered_to_a_different_address() {
+        let secp = Secp256k1::new();
+        let mut rng = OsRng::new().expect("Failed to obtain OS RNG");
+        let (secret_key, _) = secp.generate_keypair(&mut rng);
+        let (_, other_public_key) = secp.generate_keypair(&mut rng);
+        let other_address = Secp256k1RecoverableScheme::address(&other_public_key);
+
+        let message = b"canonical transfer bytes";
+        let signature = Secp256k1RecoverableScheme::sign(message, &secret_key[..]);
+
+        assert!(!Secp256k1RecoverableScheme::verify(message, &signature, &other_address));
+    }
+
+    #[test]
+    fn rejects_a_signature_over_a_different_message() {
+        let secp = Secp256k1::new();
+        let mut rng = OsRng::new().expect("Failed to obtain OS RNG");
+        let (secret_key, public_key) = secp.generate_keypair(&mut rng);
+        let address = Secp256k1RecoverableScheme::address(&public_key);
+
+        let signature = Secp256k1RecoverableScheme::sign(b"original message", &secret_key[..]);
+
+        assert!(!Secp256k1RecoverableScheme::verify(b"tampered message", &signature, &address));
+    }
+}
@@ -1,22 +1,55 @@
-use hex::{FromHex, ToHex};
-use ring::{digest, signature};
-use serde_json;
-use untrusted;
+use ring::digest;
 use uuid::Uuid;
 
 use network;
 
+use super::canonical;
+use super::memo::EncryptedBlob;
+use super::sig_scheme::{Ed25519Scheme, Secp256k1RecoverableScheme, SigScheme};
+use super::wallet::Signer;
+
+const SECP256K1_ADDRESS_HEX_LEN: usize = 40;
+
 pub const MINER_REWARD: u64 = 1;
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Transfer {
     id: Uuid,
     amount: u64,
+    fee: u64,
     sender: String,
     recipient: String,
+    last_id: String,
+    memo: Option<EncryptedBlob>,
     signature: String,
 }
 
+impl Transfer {
+    pub(crate) fn sender(&self) -> &str {
+        &self.sender
+    }
+
+    pub(crate) fn recipient(&self) -> &str {
+        &self.recipient
+    }
+
+    pub(crate) fn amount(&self) -> u64 {
+        self.amount
+    }
+
+    pub(crate) fn fee(&self) -> u64 {
+        self.fee
+    }
+
+    pub(crate) fn last_id(&self) -> &str {
+        &self.last_id
+    }
+
+    pub(crate) fn signature(&self) -> &str {
+        &self.signature
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Reward {
     id: Uuid,
@@ -24,99 +57,176 @@ pub struct Reward {
     amount: u64,
 }
 
+impl Reward {
+    pub(crate) fn recipient(&self) -> &str {
+        &self.recipient
+    }
+
+    pub(crate) fn amount(&self) -> u64 {
+        self.amount
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Transaction {
     Transfer(Transfer),
     Reward(Reward),
 }
 
-#[derive(Debug, Serialize)]
-struct SignedTransfer<'a> {
-    id: Uuid,
-    sender: &'a str,
-    recipient: &'a str,
-    amount: u64,
-}
-
 impl From<network::Transfer> for Transfer {
     fn from(transfer: network::Transfer) -> Self {
         let id = Uuid::parse_str(&transfer.id).expect("Incorrect transfer UUID");
         Self {
             id,
             amount: transfer.amount,
+            fee: transfer.fee,
             sender: transfer.sender,
             recipient: transfer.recipient,
+            last_id: transfer.last_id,
+            memo: transfer.memo,
             signature: transfer.signature,
         }
     }
 }
 
 impl Transaction {
-    pub fn transfer<S: AsRef<str>, R: AsRef<str>, P: AsRef<[u8]>>(
-        sender: S,
+    pub fn transfer<R: AsRef<str>, L: AsRef<str>>(
+        wallet: &dyn Signer,
         recipient: R,
         amount: u64,
-        private_key: P,
+        fee: u64,
+        last_id: L,
+        memo: Option<EncryptedBlob>,
     ) -> Self {
         let mut transfer = Transfer {
             id: Uuid::new_v4(),
-            sender: String::from(sender.as_ref()),
+            sender: wallet.sender_address(),
             recipient: String::from(recipient.as_ref()),
+            last_id: String::from(last_id.as_ref()),
+            memo,
             amount,
+            fee,
             signature: String::new(),
         };
-        let signature = {
-            let message = Transaction::transfer_hash(&transfer);
-            let key_pair = signature::Ed25519KeyPair::from_pkcs8(
-                untrusted::Input::from(private_key.as_ref()),
-            ).expect("Cannot create private/public key pair");
-            key_pair.sign(message.as_ref()).to_hex()
-        };
-        transfer.signature = signature;
+        transfer.signature = wallet.sign(&transfer);
         Transaction::Transfer(transfer)
     }
 
-    pub fn reward(recipient: String) -> Self {
+    /// `collected_fees` is the sum of the `fee` of every transfer included in
+    /// the same block as this reward.
+    pub fn reward(recipient: String, collected_fees: u64) -> Self {
         Transaction::Reward(Reward {
             id: Uuid::new_v4(),
             recipient,
-            amount: MINER_REWARD,
+            amount: MINER_REWARD + collected_fees,
         })
     }
 
-    pub fn is_valid(&self) -> bool {
-        match self {
-            &Transaction::Transfer(ref transfer) => Transaction::is_valid_transfer(transfer),
-            &Transaction::Reward(ref reward) => reward.amount == MINER_REWARD,
+    /// `collected_fees` is the sum of the `fee` of every transfer in the same
+    /// block as this transaction, and is only consulted for the `Reward`
+    /// arm: a `Reward` is valid only if its `amount` matches `MINER_REWARD`
+    /// plus exactly the fees its own block collected. A `Transfer` ignores
+    /// the parameter entirely. There is deliberately only one `is_valid`, so
+    /// callers can't reach for a fee-naive check and under-validate a reward.
+    pub fn is_valid(&self, collected_fees: u64) -> bool {
+        match *self {
+            Transaction::Transfer(ref transfer) => Transaction::is_valid_transfer(transfer),
+            Transaction::Reward(ref reward) => reward.amount == MINER_REWARD + collected_fees,
         }
     }
 
     fn is_valid_transfer(transfer: &Transfer) -> bool {
-        let public_key_bytes = Vec::from_hex(&transfer.sender).unwrap();
-        let signature_bytes = Vec::from_hex(&transfer.signature).unwrap();
-
-        let public_key = untrusted::Input::from(public_key_bytes.as_ref());
-        let signature = untrusted::Input::from(signature_bytes.as_ref());
         let message = Transaction::transfer_hash(transfer);
-        signature::verify(
-            &signature::ED25519,
-            public_key,
-            untrusted::Input::from(message.as_ref()),
-            signature,
-        ).is_ok()
-    }
-
-    fn transfer_hash(transfer: &Transfer) -> Vec<u8> {
-        let signed_transfer = SignedTransfer {
-            id: transfer.id,
-            sender: &transfer.sender,
-            recipient: &transfer.recipient,
-            amount: transfer.amount
-        };
-        serde_json::to_string(&signed_transfer)
-            .map(|message| digest::digest(&digest::SHA512, message.as_ref()))
-            .expect("Transactions must be able to generate a hash")
-            .as_ref()
-            .into()
+        if transfer.sender.len() == SECP256K1_ADDRESS_HEX_LEN {
+            Secp256k1RecoverableScheme::verify(&message, &transfer.signature, &transfer.sender)
+        } else {
+            Ed25519Scheme::verify(&message, &transfer.signature, &transfer.sender)
+        }
+    }
+
+    pub(crate) fn transfer_hash(transfer: &Transfer) -> Vec<u8> {
+        let message = Transaction::canonical_bytes(transfer);
+        digest::digest(&digest::SHA512, &message).as_ref().into()
+    }
+
+    /// RLP-style length-prefixed encoding of a `Transfer`'s signable fields, in a
+    /// fixed field order. Used as the signed message instead of `serde_json` so
+    /// the hash stays byte-stable across serializer versions.
+    pub fn canonical_bytes(transfer: &Transfer) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        canonical::write_bytes(&mut buffer, transfer.id.as_bytes());
+        canonical::write_bytes(&mut buffer, transfer.sender.as_bytes());
+        canonical::write_bytes(&mut buffer, transfer.recipient.as_bytes());
+        canonical::write_u64(&mut buffer, transfer.amount);
+        canonical::write_u64(&mut buffer, transfer.fee);
+        canonical::write_bytes(&mut buffer, transfer.last_id.as_bytes());
+        match &transfer.memo {
+            Some(memo) => {
+                buffer.push(1);
+                canonical::write_bytes(&mut buffer, &memo.canonical_bytes());
+            }
+            None => buffer.push(0),
+        }
+        buffer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::wallet::Wallet;
+
+    fn sample_transfer(amount: u64, fee: u64, last_id: &str) -> Transfer {
+        Transfer {
+            id: Uuid::parse_str("00000000-0000-0000-0000-000000000001").unwrap(),
+            amount,
+            fee,
+            sender: String::from("sender-address"),
+            recipient: String::from("recipient-address"),
+            last_id: String::from(last_id),
+            memo: None,
+            signature: String::new(),
+        }
+    }
+
+    #[test]
+    fn canonical_bytes_is_deterministic_for_identical_transfers() {
+        let a = Transaction::canonical_bytes(&sample_transfer(10, 1, "block-1"));
+        let b = Transaction::canonical_bytes(&sample_transfer(10, 1, "block-1"));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn canonical_bytes_changes_when_amount_fee_or_last_id_changes() {
+        let baseline = Transaction::canonical_bytes(&sample_transfer(10, 1, "block-1"));
+
+        assert_ne!(baseline, Transaction::canonical_bytes(&sample_transfer(11, 1, "block-1")));
+        assert_ne!(baseline, Transaction::canonical_bytes(&sample_transfer(10, 2, "block-1")));
+        assert_ne!(baseline, Transaction::canonical_bytes(&sample_transfer(10, 1, "block-2")));
+    }
+
+    #[test]
+    fn tampering_with_a_signed_field_invalidates_the_signature() {
+        let (wallet, _) = Wallet::generate();
+        let mut tx = Transaction::transfer(&wallet, "recipient-address", 10, 0, "block-1", None);
+        assert!(tx.is_valid(0));
+
+        if let Transaction::Transfer(ref mut transfer) = tx {
+            transfer.amount = 999;
+        }
+        assert!(!tx.is_valid(0));
+    }
+
+    #[test]
+    fn is_valid_requires_a_reward_to_match_its_block_s_collected_fees() {
+        let reward = Transaction::Reward(Reward {
+            id: Uuid::new_v4(),
+            recipient: String::from("miner-address"),
+            amount: MINER_REWARD + 5,
+        });
+
+        assert!(reward.is_valid(5));
+        assert!(!reward.is_valid(4));
+        assert!(!reward.is_valid(0));
     }
 }
@@ -0,0 +1,87 @@
+use hex::ToHex;
+use ring::{rand as ring_rand, signature};
+use secp256k1::rand::rngs::OsRng;
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use untrusted;
+
+use super::sig_scheme::{Secp256k1RecoverableScheme, SigScheme};
+use super::transaction::{Transaction, Transfer};
+
+/// A signing identity that can derive a `Transfer::sender` and produce the
+/// matching signature, independent of which `SigScheme` backs it.
+pub trait Signer {
+    fn sender_address(&self) -> String;
+    fn sign(&self, transfer: &Transfer) -> String;
+}
+
+pub struct Wallet {
+    key_pair: signature::Ed25519KeyPair,
+}
+
+impl Wallet {
+    pub fn from_pkcs8<P: AsRef<[u8]>>(pkcs8: P) -> Self {
+        let key_pair = signature::Ed25519KeyPair::from_pkcs8(untrusted::Input::from(
+            pkcs8.as_ref(),
+        )).expect("Cannot create private/public key pair");
+        Wallet { key_pair }
+    }
+
+    pub fn generate() -> (Self, Vec<u8>) {
+        let rng = ring_rand::SystemRandom::new();
+        let pkcs8_bytes =
+            signature::Ed25519KeyPair::generate_pkcs8(&rng).expect("Cannot generate key pair");
+        (Wallet::from_pkcs8(pkcs8_bytes.as_ref()), pkcs8_bytes.as_ref().to_vec())
+    }
+
+    pub fn public_key_hex(&self) -> String {
+        self.key_pair.public_key_bytes().to_hex()
+    }
+}
+
+impl Signer for Wallet {
+    fn sender_address(&self) -> String {
+        self.public_key_hex()
+    }
+
+    fn sign(&self, transfer: &Transfer) -> String {
+        let message = Transaction::transfer_hash(transfer);
+        self.key_pair.sign(message.as_ref()).to_hex()
+    }
+}
+
+/// A secp256k1 counterpart to `Wallet`: `sender_address` yields the short,
+/// recoverable address (`Secp256k1RecoverableScheme::address`) rather than a
+/// full public key, matching what `Transaction::is_valid_transfer` expects.
+pub struct Secp256k1Wallet {
+    secret_key: SecretKey,
+    public_key: PublicKey,
+}
+
+impl Secp256k1Wallet {
+    pub fn from_secret_key<K: AsRef<[u8]>>(secret_key: K) -> Self {
+        let secp = Secp256k1::signing_only();
+        let secret_key =
+            SecretKey::from_slice(secret_key.as_ref()).expect("Invalid secp256k1 private key");
+        let public_key = PublicKey::from_secret_key(&secp, &secret_key);
+        Secp256k1Wallet { secret_key, public_key }
+    }
+
+    pub fn generate() -> (Self, Vec<u8>) {
+        let secp = Secp256k1::new();
+        let mut rng = OsRng::new().expect("Failed to obtain OS RNG");
+        let (secret_key, public_key) = secp.generate_keypair(&mut rng);
+        let secret_key_bytes = secret_key[..].to_vec();
+        (Secp256k1Wallet { secret_key, public_key }, secret_key_bytes)
+    }
+}
+
+impl Signer for Secp256k1Wallet {
+    fn sender_address(&self) -> String {
+        Secp256k1RecoverableScheme::address(&self.public_key)
+    }
+
+    fn sign(&self, transfer: &Transfer) -> String {
+        let message = Transaction::transfer_hash(transfer);
+        Secp256k1RecoverableScheme::sign(&message, &self.secret_key[..])
+    }
+}
@@ -0,0 +1,16 @@
+extern crate ring;
+extern crate untrusted;
+extern crate hex;
+extern crate uuid;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate secp256k1;
+extern crate tiny_keccak;
+extern crate x25519_dalek;
+extern crate aes_gcm;
+extern crate sha2;
+extern crate rand;
+
+pub mod core;
+pub mod network;
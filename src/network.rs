@@ -0,0 +1,16 @@
+use super::core::memo::EncryptedBlob;
+
+/// Wire format for a signed transfer submitted by a client that holds its
+/// own keys: plain data, no behavior, so it can be deserialized straight off
+/// the network before being converted into a `core::transaction::Transfer`.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Transfer {
+    pub id: String,
+    pub amount: u64,
+    pub fee: u64,
+    pub sender: String,
+    pub recipient: String,
+    pub last_id: String,
+    pub memo: Option<EncryptedBlob>,
+    pub signature: String,
+}